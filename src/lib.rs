@@ -3,17 +3,47 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use opencv::{
-  core::{self, Point, Size, Vector},
+  core::{self, Point, Rect, Scalar, Size, Vector},
   imgcodecs, imgproc,
   prelude::*,
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[napi(object)]
 pub struct MatchOptions {
   pub threshold: Option<f64>,
+  /// 已废弃，请改用 `scale_min` / `scale_max`；仍会被当作两者的默认值以兼容旧调用方。
   pub scale: Option<f64>,
+  pub scale_min: Option<f64>,
+  pub scale_max: Option<f64>,
+  pub scale_steps: Option<u32>,
+  pub max_results: Option<u32>,
+  pub method: Option<String>,
+  pub region: Option<MatchRegion>,
+}
+
+/// `scale_min` 未显式给出时回退到已废弃的 `scale`，再回退到 1.0。
+fn resolve_scale_min(options: Option<&MatchOptions>) -> f64 {
+  options
+    .and_then(|o| o.scale_min.or(o.scale))
+    .unwrap_or(1.0)
+}
+
+/// `scale_max` 未显式给出时回退到已废弃的 `scale`，再回退到 1.0。
+fn resolve_scale_max(options: Option<&MatchOptions>) -> f64 {
+  options
+    .and_then(|o| o.scale_max.or(o.scale))
+    .unwrap_or(1.0)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[napi(object)]
+pub struct MatchRegion {
+  pub x: i32,
+  pub y: i32,
+  pub width: i32,
+  pub height: i32,
 }
 
 #[derive(Serialize)]
@@ -23,6 +53,165 @@ pub struct MatchResult {
   pub x: Option<i32>,
   pub y: Option<i32>,
   pub confidence: f64,
+  pub scale: f64,
+}
+
+/// `scale_steps` 的上限，避免调用方传入超大值时分配过大的 `Vec`。
+const MAX_SCALE_STEPS: u32 = 1_000;
+
+/// 在 `[scale_min, scale_max]` 区间内线性生成 `steps` 个缩放系数。
+fn scale_factors(scale_min: f64, scale_max: f64, steps: u32) -> Result<Vec<f64>> {
+  if steps > MAX_SCALE_STEPS {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("scale_steps 不能超过 {}", MAX_SCALE_STEPS),
+    ));
+  }
+
+  if steps <= 1 {
+    return Ok(vec![scale_min]);
+  }
+
+  let step = (scale_max - scale_min) / (steps - 1) as f64;
+  Ok((0..steps).map(|i| scale_min + step * i as f64).collect())
+}
+
+/// 将字符串形式的方法名解析为 OpenCV 的匹配方法常量。`TM_SQDIFF` 的原始值域是
+/// 无界的像素差平方和（越小越匹配），与另外三种归一化方法的 `[0, 1]`/`[-1, 1]`
+/// 值域不同；为了不破坏 `confidence`/`threshold` 的契约，`confidence` 会把它映射到
+/// `1 / (1 + val)`，详见匹配函数中的换算逻辑。
+fn parse_method(method: &str) -> Result<i32> {
+  match method {
+    "TM_SQDIFF" => Ok(imgproc::TM_SQDIFF),
+    "TM_SQDIFF_NORMED" => Ok(imgproc::TM_SQDIFF_NORMED),
+    "TM_CCORR_NORMED" => Ok(imgproc::TM_CCORR_NORMED),
+    "TM_CCOEFF_NORMED" => Ok(imgproc::TM_CCOEFF_NORMED),
+    other => Err(Error::new(
+      Status::InvalidArg,
+      format!("不支持的匹配方法: {}", other),
+    )),
+  }
+}
+
+/// 带掩膜的匹配只支持 `TM_SQDIFF` 与 `TM_CCORR_NORMED`（OpenCV 自身限制）。
+fn method_allows_mask(method: i32) -> bool {
+  method == imgproc::TM_SQDIFF || method == imgproc::TM_CCORR_NORMED
+}
+
+/// `TM_SQDIFF`/`TM_SQDIFF_NORMED` 是越小越匹配，其余方法越大越匹配。
+fn is_sqdiff_family(method: i32) -> bool {
+  method == imgproc::TM_SQDIFF || method == imgproc::TM_SQDIFF_NORMED
+}
+
+/// 把 `match_template` 的原始输出换算为越大越匹配的 `confidence`：
+/// `TM_SQDIFF_NORMED` 本身是 `[0, 1]` 区间的误差比例，取 `1 - val`；`TM_SQDIFF`
+/// 是无界的像素差平方和，用 `1 / (1 + val)` 压缩到 `(0, 1]`；其余方法本身已是
+/// 归一化的相似度，直接使用（`TM_CCORR_NORMED` 为 `[0, 1]`，`TM_CCOEFF_NORMED`
+/// 为 `[-1, 1]`）。
+fn sqdiff_confidence(method: i32, val: f64) -> f64 {
+  if method == imgproc::TM_SQDIFF_NORMED {
+    1.0 - val
+  } else if method == imgproc::TM_SQDIFF {
+    1.0 / (1.0 + val)
+  } else {
+    val
+  }
+}
+
+/// 若模板图带 alpha 通道，拆分出 BGR 部分与 alpha 掩膜；否则转换为 BGR 并返回空
+/// 掩膜。因为用 `IMREAD_UNCHANGED` 解码模板图后通道数不再固定为 3（灰度图是 1
+/// 通道、灰度+alpha 是 2 通道），必须显式转换，否则会和恒为 3 通道 BGR 的大图
+/// 通道数不匹配，导致 `match_template` 失败。
+fn split_needle_mask(needle: &Mat) -> Result<(Mat, Mat)> {
+  match needle.channels() {
+    4 => {
+      let mut planes = Vector::<Mat>::new();
+      core::split(needle, &mut planes)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("拆分模板图通道失败: {}", e)))?;
+
+      let mask = planes.get(3).map_err(|e| Error::from_reason(e.to_string()))?;
+
+      let mut bgr = Vector::<Mat>::new();
+      bgr.push(planes.get(0).map_err(|e| Error::from_reason(e.to_string()))?);
+      bgr.push(planes.get(1).map_err(|e| Error::from_reason(e.to_string()))?);
+      bgr.push(planes.get(2).map_err(|e| Error::from_reason(e.to_string()))?);
+
+      let mut color = Mat::default();
+      core::merge(&bgr, &mut color)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("合并模板图通道失败: {}", e)))?;
+
+      Ok((color, mask))
+    }
+    3 => Ok((needle.clone(), Mat::default())),
+    2 => {
+      let mut planes = Vector::<Mat>::new();
+      core::split(needle, &mut planes)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("拆分模板图通道失败: {}", e)))?;
+
+      let gray = planes.get(0).map_err(|e| Error::from_reason(e.to_string()))?;
+      let mask = planes.get(1).map_err(|e| Error::from_reason(e.to_string()))?;
+
+      let mut color = Mat::default();
+      imgproc::cvt_color(&gray, &mut color, imgproc::COLOR_GRAY2BGR, 0)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("转换模板图颜色空间失败: {}", e)))?;
+
+      Ok((color, mask))
+    }
+    _ => {
+      let mut color = Mat::default();
+      imgproc::cvt_color(needle, &mut color, imgproc::COLOR_GRAY2BGR, 0)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("转换模板图颜色空间失败: {}", e)))?;
+
+      Ok((color, Mat::default()))
+    }
+  }
+}
+
+/// 计算以命中点 `loc` 为中心、与模板图同宽高的抑制窗口，并裁剪到结果矩阵
+/// `[0, result_cols) x [0, result_rows)` 范围内，供非极大值抑制写入哨兵值。
+fn suppression_rect(loc: Point, needle_size: Size, result_cols: i32, result_rows: i32) -> Rect {
+  let left = loc.x - needle_size.width / 2;
+  let top = loc.y - needle_size.height / 2;
+  let x0 = left.max(0);
+  let y0 = top.max(0);
+  let x1 = (left + needle_size.width).min(result_cols);
+  let y1 = (top + needle_size.height).min(result_rows);
+
+  Rect::new(x0, y0, (x1 - x0).max(1), (y1 - y0).max(1))
+}
+
+/// 按可选的 `region` 裁剪大图，返回裁剪后的子图以及裁剪区域左上角在原图中的偏移
+/// （未指定 `region` 时即为整张大图，偏移为 `(0, 0)`）。
+fn crop_to_region(haystack: &Mat, region: Option<MatchRegion>) -> Result<(Mat, Point)> {
+  let region_rect = match region {
+    Some(r) => {
+      // 用 i64 计算右/下边界，避免调用方传入超大 region 时 i32 加法溢出绕过校验。
+      let x_end = r.x as i64 + r.width as i64;
+      let y_end = r.y as i64 + r.height as i64;
+
+      if r.width <= 0
+        || r.height <= 0
+        || r.x < 0
+        || r.y < 0
+        || x_end > haystack.cols() as i64
+        || y_end > haystack.rows() as i64
+      {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "region 超出了大图范围".to_string(),
+        ));
+      }
+
+      Rect::new(r.x, r.y, r.width, r.height)
+    }
+    None => Rect::new(0, 0, haystack.cols(), haystack.rows()),
+  };
+
+  let search_area = haystack
+    .roi(region_rect)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("裁剪 region 失败: {}", e)))?;
+
+  Ok((search_area, Point::new(region_rect.x, region_rect.y)))
 }
 
 #[napi]
@@ -32,7 +221,197 @@ pub fn template_match(
   options: Option<MatchOptions>,
 ) -> Result<MatchResult> {
   let threshold = options.as_ref().and_then(|o| o.threshold).unwrap_or(0.8);
-  let scale = options.as_ref().and_then(|o| o.scale).unwrap_or(1.0);
+  let scale_min = resolve_scale_min(options.as_ref());
+  let scale_max = resolve_scale_max(options.as_ref());
+  let scale_steps = options.as_ref().and_then(|o| o.scale_steps).unwrap_or(1);
+  let method = parse_method(
+    options
+      .as_ref()
+      .and_then(|o| o.method.as_deref())
+      .unwrap_or("TM_CCOEFF_NORMED"),
+  )?;
+
+  let haystack_data: Vec<u8> = haystack_buffer.to_vec();
+  let needle_data: Vec<u8> = needle_buffer.to_vec();
+
+  let haystack_vector = Vector::<u8>::from_iter(haystack_data);
+  let needle_vector = Vector::<u8>::from_iter(needle_data);
+
+  let haystack = imgcodecs::imdecode(&haystack_vector, imgcodecs::IMREAD_COLOR)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("解码大图失败: {}", e)))?;
+
+  let needle_raw = imgcodecs::imdecode(&needle_vector, imgcodecs::IMREAD_UNCHANGED)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("解码模板图失败: {}", e)))?;
+
+  if haystack.empty() || needle_raw.empty() {
+    return Err(Error::new(Status::InvalidArg, "图片数据为空".to_string()));
+  }
+
+  let (needle, mask) = split_needle_mask(&needle_raw)?;
+
+  if !mask.empty() && !method_allows_mask(method) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "带透明通道的模板图只能配合 TM_SQDIFF 或 TM_CCORR_NORMED 使用".to_string(),
+    ));
+  }
+
+  let region = options.as_ref().and_then(|o| o.region);
+  let (search_area, region_offset) = crop_to_region(&haystack, region)?;
+
+  let haystack_size = search_area
+    .size()
+    .map_err(|e| Error::new(Status::GenericFailure, format!("获取大图尺寸失败: {}", e)))?;
+
+  let is_sqdiff_normed = is_sqdiff_family(method);
+
+  let mut best: Option<(f64, Point, f64)> = None;
+
+  for factor in scale_factors(scale_min, scale_max, scale_steps)? {
+    let new_size = Size::new(
+      (needle.cols() as f64 * factor) as i32,
+      (needle.rows() as f64 * factor) as i32,
+    );
+
+    if new_size.width <= 0 || new_size.height <= 0 {
+      continue;
+    }
+
+    let mut resized_needle = Mat::default();
+    imgproc::resize(
+      &needle,
+      &mut resized_needle,
+      new_size,
+      0.0,
+      0.0,
+      imgproc::INTER_LINEAR,
+    )
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+
+    let needle_size = resized_needle
+      .size()
+      .map_err(|e| Error::from_reason(e.to_string()))?;
+
+    if haystack_size.width < needle_size.width || haystack_size.height < needle_size.height {
+      continue;
+    }
+
+    let resized_mask = if mask.empty() {
+      Mat::default()
+    } else {
+      let mut resized_mask = Mat::default();
+      imgproc::resize(
+        &mask,
+        &mut resized_mask,
+        new_size,
+        0.0,
+        0.0,
+        imgproc::INTER_LINEAR,
+      )
+      .map_err(|e| Error::from_reason(e.to_string()))?;
+      resized_mask
+    };
+
+    let result_cols = haystack_size.width - needle_size.width + 1;
+    let result_rows = haystack_size.height - needle_size.height + 1;
+
+    let mut result = Mat::default();
+
+    unsafe {
+      result
+        .create_size(Size::new(result_cols, result_rows), core::CV_32FC1)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("创建结果矩阵失败: {}", e)))?;
+    }
+
+    imgproc::match_template(
+      &search_area,
+      &resized_needle,
+      &mut result,
+      method,
+      &resized_mask,
+    )
+    .map_err(|e| Error::new(Status::GenericFailure, format!("模板匹配失败: {}", e)))?;
+
+    let mut min_val = 0.0;
+    let mut max_val = 0.0;
+    let mut min_loc = Point::default();
+    let mut max_loc = Point::default();
+
+    core::min_max_loc(
+      &result,
+      Some(&mut min_val),
+      Some(&mut max_val),
+      Some(&mut min_loc),
+      Some(&mut max_loc),
+      &Mat::default(),
+    )
+    .map_err(|e| Error::new(Status::GenericFailure, format!("查找最值失败: {}", e)))?;
+
+    let (val, loc) = if is_sqdiff_normed {
+      (min_val, min_loc)
+    } else {
+      (max_val, max_loc)
+    };
+
+    let is_better = match best {
+      None => true,
+      Some((best_val, _, _)) => {
+        if is_sqdiff_normed {
+          val < best_val
+        } else {
+          val > best_val
+        }
+      }
+    };
+
+    if is_better {
+      best = Some((val, loc, factor));
+    }
+  }
+
+  let (val, loc, scale) = best.ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      "缩放区间内没有可用的尺寸，模板图在所有缩放比例下都大于大图或缩放后尺寸为 0".to_string(),
+    )
+  })?;
+
+  let confidence = sqdiff_confidence(method, val);
+
+  let matched = confidence >= threshold;
+  let loc = Point::new(loc.x + region_offset.x, loc.y + region_offset.y);
+
+  Ok(MatchResult {
+    found: matched,
+    x: if matched { Some(loc.x) } else { None },
+    y: if matched { Some(loc.y) } else { None },
+    confidence,
+    scale,
+  })
+}
+
+/// 在大图中查找所有高于 `threshold` 的匹配，使用贪心非极大值抑制去除聚集在同一
+/// 目标周围的重复命中。单一缩放比例（取 `scale_min`，默认 1.0），如需多尺度搜索
+/// 请调用 `template_match`。`method`、`region` 与带 alpha 掩膜的模板图均与
+/// `template_match` 行为一致。
+#[napi]
+pub fn template_match_all(
+  haystack_buffer: Buffer,
+  needle_buffer: Buffer,
+  options: Option<MatchOptions>,
+) -> Result<Vec<MatchResult>> {
+  let threshold = options.as_ref().and_then(|o| o.threshold).unwrap_or(0.8);
+  let scale = resolve_scale_min(options.as_ref());
+  let max_results = options
+    .as_ref()
+    .and_then(|o| o.max_results)
+    .unwrap_or(u32::MAX);
+  let method = parse_method(
+    options
+      .as_ref()
+      .and_then(|o| o.method.as_deref())
+      .unwrap_or("TM_CCOEFF_NORMED"),
+  )?;
 
   let haystack_data: Vec<u8> = haystack_buffer.to_vec();
   let needle_data: Vec<u8> = needle_buffer.to_vec();
@@ -43,14 +422,26 @@ pub fn template_match(
   let haystack = imgcodecs::imdecode(&haystack_vector, imgcodecs::IMREAD_COLOR)
     .map_err(|e| Error::new(Status::GenericFailure, format!("解码大图失败: {}", e)))?;
 
-  let needle = imgcodecs::imdecode(&needle_vector, imgcodecs::IMREAD_COLOR)
+  let needle_raw = imgcodecs::imdecode(&needle_vector, imgcodecs::IMREAD_UNCHANGED)
     .map_err(|e| Error::new(Status::GenericFailure, format!("解码模板图失败: {}", e)))?;
 
-  if haystack.empty() || needle.empty() {
+  if haystack.empty() || needle_raw.empty() {
     return Err(Error::new(Status::InvalidArg, "图片数据为空".to_string()));
   }
 
-  let haystack_size = haystack
+  let (needle, mask) = split_needle_mask(&needle_raw)?;
+
+  if !mask.empty() && !method_allows_mask(method) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "带透明通道的模板图只能配合 TM_SQDIFF 或 TM_CCORR_NORMED 使用".to_string(),
+    ));
+  }
+
+  let region = options.as_ref().and_then(|o| o.region);
+  let (search_area, region_offset) = crop_to_region(&haystack, region)?;
+
+  let haystack_size = search_area
     .size()
     .map_err(|e| Error::new(Status::GenericFailure, format!("获取大图尺寸失败: {}", e)))?;
 
@@ -81,6 +472,22 @@ pub fn template_match(
     ));
   }
 
+  let resized_mask = if mask.empty() {
+    Mat::default()
+  } else {
+    let mut resized_mask = Mat::default();
+    imgproc::resize(
+      &mask,
+      &mut resized_mask,
+      new_size,
+      0.0,
+      0.0,
+      imgproc::INTER_LINEAR,
+    )
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+    resized_mask
+  };
+
   let result_cols = haystack_size.width - needle_size.width + 1;
   let result_rows = haystack_size.height - needle_size.height + 1;
 
@@ -93,35 +500,227 @@ pub fn template_match(
   }
 
   imgproc::match_template(
-    &haystack,
+    &search_area,
     &resized_needle,
     &mut result,
-    imgproc::TM_CCOEFF_NORMED,
-    &Mat::default(),
+    method,
+    &resized_mask,
   )
   .map_err(|e| Error::new(Status::GenericFailure, format!("模板匹配失败: {}", e)))?;
 
-  let mut min_val = 0.0;
-  let mut max_val = 0.0;
-  let mut min_loc = Point::default();
-  let mut max_loc = Point::default();
-
-  core::min_max_loc(
-    &result,
-    Some(&mut min_val),
-    Some(&mut max_val),
-    Some(&mut min_loc),
-    Some(&mut max_loc),
-    &Mat::default(),
-  )
-  .map_err(|e| Error::new(Status::GenericFailure, format!("查找最值失败: {}", e)))?;
+  let is_sqdiff_normed = is_sqdiff_family(method);
+  // 非极大值抑制的“哨兵”值：CCORR/CCOEFF 取最大值，抑制时写入值域下限 -1.0；
+  // SQDIFF/SQDIFF_NORMED 取最小值，抑制时写入一个大于其值域上限的哨兵，使其不再被选中。
+  let suppress_sentinel = if is_sqdiff_normed { f32::MAX as f64 } else { -1.0 };
 
-  let matched = max_val >= threshold;
+  let mut matches = Vec::new();
 
-  Ok(MatchResult {
-    found: matched,
-    x: if matched { Some(max_loc.x) } else { None },
-    y: if matched { Some(max_loc.y) } else { None },
-    confidence: max_val,
-  })
+  loop {
+    if matches.len() as u32 >= max_results {
+      break;
+    }
+
+    let mut min_val = 0.0;
+    let mut max_val = 0.0;
+    let mut min_loc = Point::default();
+    let mut max_loc = Point::default();
+
+    core::min_max_loc(
+      &result,
+      Some(&mut min_val),
+      Some(&mut max_val),
+      Some(&mut min_loc),
+      Some(&mut max_loc),
+      &Mat::default(),
+    )
+    .map_err(|e| Error::new(Status::GenericFailure, format!("查找最值失败: {}", e)))?;
+
+    let (val, loc) = if is_sqdiff_normed {
+      (min_val, min_loc)
+    } else {
+      (max_val, max_loc)
+    };
+
+    let confidence = sqdiff_confidence(method, val);
+
+    if confidence < threshold {
+      break;
+    }
+
+    matches.push(MatchResult {
+      found: true,
+      x: Some(loc.x + region_offset.x),
+      y: Some(loc.y + region_offset.y),
+      confidence,
+      scale,
+    });
+
+    let suppress_rect = suppression_rect(loc, needle_size, result_cols, result_rows);
+    let mut suppress_roi = result
+      .roi_mut(suppress_rect)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("抑制命中区域失败: {}", e)))?;
+
+    suppress_roi
+      .set_to(&Scalar::all(suppress_sentinel), &Mat::default())
+      .map_err(|e| Error::new(Status::GenericFailure, format!("抑制命中区域失败: {}", e)))?;
+  }
+
+  Ok(matches)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[napi(object)]
+pub struct AnnotateColor {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[napi(object)]
+pub struct AnnotateOptions {
+  pub format: Option<String>,
+  pub quality: Option<u32>,
+  pub color: Option<AnnotateColor>,
+  pub thickness: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[napi(object)]
+pub struct AnnotatedMatch {
+  pub result: MatchResult,
+  pub image: Buffer,
+}
+
+/// 按 `format`（`"png"` 或 `"jpeg"`）将标注后的大图编码为字节流。
+fn encode_annotated(image: &Mat, annotate: &AnnotateOptions) -> Result<Buffer> {
+  let format = annotate.format.as_deref().unwrap_or("png");
+
+  let (ext, params) = match format {
+    "png" => (".png", Vector::<i32>::new()),
+    "jpeg" => {
+      let quality = annotate.quality.unwrap_or(90).clamp(1, 100) as i32;
+      (
+        ".jpg",
+        Vector::<i32>::from_iter([imgcodecs::IMWRITE_JPEG_QUALITY, quality]),
+      )
+    }
+    other => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("不支持的图片格式: {}", other),
+      ))
+    }
+  };
+
+  let mut encoded = Vector::<u8>::new();
+  imgcodecs::imencode(ext, image, &mut encoded, &params)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("编码结果图失败: {}", e)))?;
+
+  Ok(Buffer::from(encoded.to_vec()))
+}
+
+/// 在匹配位置画出矩形框并返回带标注的结果图，供 Node 端直接展示调试用图片，
+/// 无需再经过一次 OpenCV 往返。
+#[napi]
+pub fn template_match_annotated(
+  haystack_buffer: Buffer,
+  needle_buffer: Buffer,
+  options: Option<MatchOptions>,
+  annotate: Option<AnnotateOptions>,
+) -> Result<AnnotatedMatch> {
+  let annotate = annotate.unwrap_or(AnnotateOptions {
+    format: None,
+    quality: None,
+    color: None,
+    thickness: None,
+  });
+
+  let haystack_bytes: Vec<u8> = haystack_buffer.to_vec();
+  let needle_bytes: Vec<u8> = needle_buffer.to_vec();
+
+  let result = template_match(
+    Buffer::from(haystack_bytes.clone()),
+    Buffer::from(needle_bytes.clone()),
+    options,
+  )?;
+
+  let haystack_vector = Vector::<u8>::from_iter(haystack_bytes);
+  let mut canvas = imgcodecs::imdecode(&haystack_vector, imgcodecs::IMREAD_COLOR)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("解码大图失败: {}", e)))?;
+
+  if result.found {
+    let needle_vector = Vector::<u8>::from_iter(needle_bytes);
+    let needle = imgcodecs::imdecode(&needle_vector, imgcodecs::IMREAD_COLOR)
+      .map_err(|e| Error::new(Status::GenericFailure, format!("解码模板图失败: {}", e)))?;
+
+    let needle_width = (needle.cols() as f64 * result.scale).round() as i32;
+    let needle_height = (needle.rows() as f64 * result.scale).round() as i32;
+
+    let color = annotate.color.unwrap_or(AnnotateColor { r: 0, g: 255, b: 0 });
+    let thickness = annotate.thickness.unwrap_or(2);
+
+    imgproc::rectangle(
+      &mut canvas,
+      Rect::new(
+        result.x.unwrap_or(0),
+        result.y.unwrap_or(0),
+        needle_width,
+        needle_height,
+      ),
+      Scalar::new(color.b as f64, color.g as f64, color.r as f64, 0.0),
+      thickness,
+      imgproc::LINE_8,
+      0,
+    )
+    .map_err(|e| Error::new(Status::GenericFailure, format!("绘制标注框失败: {}", e)))?;
+  }
+
+  let image = encode_annotated(&canvas, &annotate)?;
+
+  Ok(AnnotatedMatch { result, image })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn scale_factors_single_step_returns_scale_min() {
+    let factors = scale_factors(0.5, 2.0, 1).unwrap();
+    assert_eq!(factors, vec![0.5]);
+
+    let factors = scale_factors(1.0, 1.0, 0).unwrap();
+    assert_eq!(factors, vec![1.0]);
+  }
+
+  #[test]
+  fn scale_factors_linear_spacing() {
+    let factors = scale_factors(1.0, 2.0, 3).unwrap();
+    assert_eq!(factors, vec![1.0, 1.5, 2.0]);
+  }
+
+  #[test]
+  fn scale_factors_rejects_too_many_steps() {
+    let err = scale_factors(1.0, 1.0, MAX_SCALE_STEPS + 1).unwrap_err();
+    assert_eq!(err.status, Status::InvalidArg);
+  }
+
+  #[test]
+  fn suppression_rect_centers_on_hit() {
+    let rect = suppression_rect(Point::new(50, 50), Size::new(20, 10), 200, 100);
+    assert_eq!(rect, Rect::new(40, 45, 20, 10));
+  }
+
+  #[test]
+  fn suppression_rect_clamps_at_top_left_edge() {
+    let rect = suppression_rect(Point::new(2, 1), Size::new(20, 10), 200, 100);
+    assert_eq!(rect, Rect::new(0, 0, 12, 6));
+  }
+
+  #[test]
+  fn suppression_rect_clamps_at_bottom_right_edge() {
+    let rect = suppression_rect(Point::new(198, 98), Size::new(20, 10), 200, 100);
+    assert_eq!(rect, Rect::new(188, 93, 12, 7));
+  }
 }